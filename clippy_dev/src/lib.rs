@@ -10,9 +10,15 @@
 
 #![allow(clippy::default_hash_types)]
 
+pub mod new_lint;
+pub mod serve;
+pub mod update_lints;
+
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
+use rustc_lexer::unescape::{unescape_raw_str, unescape_str};
+use rustc_lexer::{tokenize, TokenKind};
 use walkdir::WalkDir;
 use std::collections::HashMap;
 use std::ffi::OsStr;
@@ -20,18 +26,6 @@ use std::fs;
 use std::io::prelude::*;
 
 lazy_static! {
-    static ref DEC_CLIPPY_LINT_RE: Regex = Regex::new(r#"(?x)
-        declare_clippy_lint!\s*[\{(]\s*
-        pub\s+(?P<name>[A-Z_][A-Z_0-9]*)\s*,\s*
-        (?P<cat>[a-z_]+)\s*,\s*
-        "(?P<desc>(?:[^"\\]+|\\(?s).(?-s))*)"\s*[})]
-    "#).unwrap();
-    static ref DEC_DEPRECATED_LINT_RE: Regex = Regex::new(r#"(?x)
-        declare_deprecated_lint!\s*[{(]\s*
-        pub\s+(?P<name>[A-Z_][A-Z_0-9]*)\s*,\s*
-        "(?P<desc>(?:[^"\\]+|\\(?s).(?-s))*)"\s*[})]
-    "#).unwrap();
-    static ref NL_ESCAPE_RE: Regex = Regex::new(r#"\\\n\s*"#).unwrap();
     pub static ref DOCS_LINK: String = "https://rust-lang-nursery.github.io/rust-clippy/master/index.html".to_string();
 }
 
@@ -43,16 +37,18 @@ pub struct Lint {
     pub desc: String,
     pub deprecation: Option<String>,
     pub module: String,
+    pub version: Option<String>,
 }
 
 impl Lint {
-    pub fn new(name: &str, group: &str, desc: &str, deprecation: Option<&str>, module: &str) -> Self {
+    pub fn new(name: &str, group: &str, desc: &str, deprecation: Option<&str>, module: &str, version: Option<&str>) -> Self {
         Self {
             name: name.to_lowercase(),
             group: group.to_string(),
-            desc: NL_ESCAPE_RE.replace(&desc.replace("\\\"", "\""), "").to_string(),
+            desc: desc.to_string(),
             deprecation: deprecation.map(|d| d.to_string()),
             module: module.to_string(),
+            version: version.map(|v| v.to_string()),
         }
     }
 
@@ -86,6 +82,46 @@ pub fn gen_changelog_lint_list(lints: Vec<Lint>) -> Vec<String> {
         }).collect()
 }
 
+/// Generates the "new lints" changelog section, grouping lints by the
+/// `#[clippy::version]` they were introduced in. Lints without a version
+/// attribute are collected under an `Unknown` heading so they still show up
+/// and can be spotted as missing metadata.
+pub fn gen_versions_lint_list(lints: Vec<Lint>) -> Vec<String> {
+    let mut lints_by_version: HashMap<String, Vec<Lint>> = HashMap::new();
+    for lint in lints {
+        let version = lint.version.clone().unwrap_or_else(|| "Unknown".to_string());
+        lints_by_version.entry(version).or_insert_with(Vec::new).push(lint);
+    }
+
+    let mut versions: Vec<&String> = lints_by_version.keys().collect();
+    versions.sort_by_key(|version| version_sort_key(version));
+
+    versions
+        .into_iter()
+        .flat_map(|version| {
+            let mut lints = lints_by_version[version].clone();
+            lints.sort_by_key(|l| l.name.clone());
+            std::iter::once(format!("## {}", version))
+                .chain(lints.into_iter().map(|l| format!("* [`{}`]: {}#{}", l.name, DOCS_LINK.clone(), l.name)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Parses a `major.minor.patch` version string into a tuple that sorts
+/// numerically instead of lexicographically (so `1.9.0` sorts before
+/// `1.10.0`). Unparsable components (including the `Unknown` placeholder
+/// used for lints without a `#[clippy::version]`) sort after every real
+/// version.
+fn version_sort_key(version: &str) -> (u32, u32, u32) {
+    let mut parts = version.split('.').map(|part| part.parse().unwrap_or(u32::MAX));
+    (
+        parts.next().unwrap_or(u32::MAX),
+        parts.next().unwrap_or(u32::MAX),
+        parts.next().unwrap_or(u32::MAX),
+    )
+}
+
 /// Generates the `register_removed` code in `./clippy_lints/src/lib.rs`.
 pub fn gen_deprecated(lints: &[Lint]) -> Vec<String> {
     lints.iter()
@@ -115,19 +151,156 @@ fn gather_from_file(dir_entry: &walkdir::DirEntry) -> impl Iterator<Item=Lint> {
     parse_contents(&content, dir_entry.path().file_stem().unwrap().to_str().unwrap())
 }
 
+/// A single lexed token together with the source text it spans.
+struct SpannedToken<'a> {
+    kind: TokenKind,
+    text: &'a str,
+}
+
+/// Lexes `content` into a flat list of tokens with their matching source
+/// text, dropping whitespace and comments so the rest of the parser can walk
+/// over the meaningful tokens of a `declare_clippy_lint!` invocation without
+/// worrying about doc comments or formatting in between.
+fn lex(content: &str) -> Vec<SpannedToken<'_>> {
+    let mut offset = 0;
+    tokenize(content)
+        .filter_map(|token| {
+            let text = &content[offset..offset + token.len];
+            offset += token.len;
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment { .. } => None,
+                kind => Some(SpannedToken { kind, text }),
+            }
+        })
+        .collect()
+}
+
+/// Decodes a string or raw string `Literal` token into its represented
+/// value, so that escaped quotes (`\"`) and raw strings (`r#"..."#`) are
+/// handled the same way rustc itself would handle them.
+fn unescape_literal(text: &str) -> String {
+    if let Some(rest) = text.strip_prefix('r') {
+        let hashes = rest.chars().take_while(|&c| c == '#').count();
+        let inner = &rest[hashes + 1..rest.len() - hashes - 1];
+        let mut desc = String::with_capacity(inner.len());
+        unescape_raw_str(inner, &mut |_, result| {
+            if let Ok(c) = result {
+                desc.push(c);
+            }
+        });
+        desc
+    } else {
+        let inner = &text[1..text.len() - 1];
+        let mut desc = String::with_capacity(inner.len());
+        unescape_str(inner, &mut |_, result| {
+            if let Ok(c) = result {
+                desc.push(c);
+            }
+        });
+        desc
+    }
+}
+
+/// Parses a single `declare_clippy_lint! { pub NAME, category, "desc" }` (or
+/// `declare_deprecated_lint!`) invocation starting right after the macro
+/// name, returning the resulting `Lint` and the index of the token after the
+/// description literal.
+fn parse_lint_invocation(tokens: &[SpannedToken<'_>], start: usize, deprecated: bool, filename: &str) -> Option<(Lint, usize)> {
+    let mut i = start;
+    if tokens.get(i)?.kind != TokenKind::Not {
+        return None;
+    }
+    i += 1;
+    if !matches!(tokens.get(i)?.kind, TokenKind::OpenBrace | TokenKind::OpenParen) {
+        return None;
+    }
+    i += 1;
+
+    let version = parse_version_attr(tokens, &mut i);
+
+    // `pub`
+    i += 1;
+    let name = tokens.get(i)?.text;
+    i += 1;
+    // `,`
+    i += 1;
+
+    let group = if deprecated {
+        "Deprecated".to_string()
+    } else {
+        let cat = tokens.get(i)?.text.to_string();
+        i += 1;
+        // `,`
+        i += 1;
+        cat
+    };
+
+    let desc = unescape_literal(tokens.get(i)?.text);
+
+    let deprecation = if deprecated { Some(desc.clone()) } else { None };
+    Some((Lint::new(name, &group, &desc, deprecation.as_deref(), filename, version.as_deref()), i + 1))
+}
+
+/// Consumes an optional `#[clippy::version = "..."]` attribute starting at
+/// `*i`, advancing `*i` past it and returning the decoded version string.
+/// Leaves `*i` untouched if no such attribute is present.
+fn parse_version_attr(tokens: &[SpannedToken<'_>], i: &mut usize) -> Option<String> {
+    let mut j = *i;
+    if tokens.get(j)?.kind != TokenKind::Pound {
+        return None;
+    }
+    j += 1;
+    if tokens.get(j)?.kind != TokenKind::OpenBracket {
+        return None;
+    }
+    j += 1;
+    if tokens.get(j)?.text != "clippy" {
+        return None;
+    }
+    j += 1;
+    // `::`
+    j += 2;
+    if tokens.get(j)?.text != "version" {
+        return None;
+    }
+    j += 1;
+    // `=`
+    j += 1;
+    let version = unescape_literal(tokens.get(j)?.text);
+    j += 1;
+    if tokens.get(j)?.kind != TokenKind::CloseBracket {
+        return None;
+    }
+    *i = j + 1;
+    Some(version)
+}
+
 fn parse_contents(content: &str, filename: &str) -> impl Iterator<Item=Lint> {
-    let lints = DEC_CLIPPY_LINT_RE
-        .captures_iter(content)
-        .map(|m| Lint::new(&m["name"], &m["cat"], &m["desc"], None, filename));
-    let deprecated = DEC_DEPRECATED_LINT_RE
-        .captures_iter(content)
-        .map(|m| Lint::new( &m["name"], "Deprecated", &m["desc"], Some(&m["desc"]), filename));
-    // Removing the `.collect::<Vec<Lint>>().into_iter()` causes some lifetime issues due to the map
-    lints.chain(deprecated).collect::<Vec<Lint>>().into_iter()
+    let tokens = lex(content);
+    let mut lints = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_deprecated = match tokens[i].kind {
+            TokenKind::Ident if tokens[i].text == "declare_clippy_lint" => false,
+            TokenKind::Ident if tokens[i].text == "declare_deprecated_lint" => true,
+            _ => {
+                i += 1;
+                continue;
+            },
+        };
+        match parse_lint_invocation(&tokens, i + 1, is_deprecated, filename) {
+            Some((lint, next)) => {
+                lints.push(lint);
+                i = next;
+            },
+            None => i += 1,
+        }
+    }
+    lints.into_iter()
 }
 
 /// Collects all .rs files in the `clippy_lints/src` directory
-fn lint_files() -> impl Iterator<Item=walkdir::DirEntry> {
+pub(crate) fn lint_files() -> impl Iterator<Item=walkdir::DirEntry> {
     // We use `WalkDir` instead of `fs::read_dir` here in order to recurse into subdirectories.
     // Otherwise we would not collect all the lints, for example in `clippy_lints/src/methods/`.
     WalkDir::new("../clippy_lints/src")
@@ -212,9 +385,10 @@ pub fn replace_region_in_text<F>(text: &str, start: &str, end: &str, replace_sta
 
     if !found {
         // This happens if the provided regex in `clippy_dev/src/main.rs` is not found in the
-        // given text or file. Most likely this is an error on the programmer's side and the Regex
-        // is incorrect.
-        println!("regex {:?} not found. You may have to update it.", start);
+        // given text or file, or the region markers it's supposed to delimit were never added to
+        // begin with. Silently doing nothing here would make every caller report success while
+        // leaving the file unchanged, so fail loudly instead.
+        panic!("region marker {:?} not found, code generation was not performed", start);
     }
     new_lines.join("\n")
 }
@@ -222,7 +396,7 @@ pub fn replace_region_in_text<F>(text: &str, start: &str, end: &str, replace_sta
 #[test]
 fn test_parse_contents() {
     let result: Vec<Lint> = parse_contents(
-        r#"
+        r##"
 declare_clippy_lint! {
     pub PTR_ARG,
     style,
@@ -236,23 +410,54 @@ declare_clippy_lint!{
     "single line"
 }
 
+declare_clippy_lint! {
+    /// some doc comment
+    #[clippy::version = "1.29.0"]
+    pub WITH_VERSION,
+    correctness,
+    "has a version attribute"
+}
+
+declare_clippy_lint! {
+    pub RAW_DESC,
+    style,
+    r#"contains a \" backslash-quote and a } brace"#
+}
+
+declare_clippy_lint! {
+    pub ESCAPED_DESC,
+    style,
+    "contains \"escaped\" quotes"
+}
+
 /// some doc comment
 declare_deprecated_lint! {
     pub SHOULD_ASSERT_EQ,
     "`assert!()` will be more flexible with RFC 2011"
 }
-    "#,
+    "##,
     "module_name").collect();
 
     let expected = vec![
-        Lint::new("ptr_arg", "style", "really long text", None, "module_name"),
-        Lint::new("doc_markdown", "pedantic", "single line", None, "module_name"),
+        Lint::new("ptr_arg", "style", "really long text", None, "module_name", None),
+        Lint::new("doc_markdown", "pedantic", "single line", None, "module_name", None),
+        Lint::new("with_version", "correctness", "has a version attribute", None, "module_name", Some("1.29.0")),
+        Lint::new(
+            "raw_desc",
+            "style",
+            r#"contains a \" backslash-quote and a } brace"#,
+            None,
+            "module_name",
+            None,
+        ),
+        Lint::new("escaped_desc", "style", r#"contains "escaped" quotes"#, None, "module_name", None),
         Lint::new(
             "should_assert_eq",
             "Deprecated",
             "`assert!()` will be more flexible with RFC 2011",
             Some("`assert!()` will be more flexible with RFC 2011"),
-            "module_name"
+            "module_name",
+            None,
         ),
     ];
     assert_eq!(expected, result);
@@ -295,16 +500,23 @@ ghi"#;
     assert_eq!(expected, result);
 }
 
+#[test]
+#[should_panic(expected = "region marker")]
+fn test_replace_region_missing_marker_panics() {
+    let text = "abc\n123\ndef";
+    replace_region_in_text(text, r#"^\s*does_not_exist$"#, r#"^\s*def"#, false, || vec![]);
+}
+
 #[test]
 fn test_usable_lints() {
     let lints = vec![
-        Lint::new("should_assert_eq", "Deprecated", "abc", Some("Reason"), "module_name"),
-        Lint::new("should_assert_eq2", "Not Deprecated", "abc", None, "module_name"),
-        Lint::new("should_assert_eq2", "internal", "abc", None, "module_name"),
-        Lint::new("should_assert_eq2", "internal_style", "abc", None, "module_name")
+        Lint::new("should_assert_eq", "Deprecated", "abc", Some("Reason"), "module_name", None),
+        Lint::new("should_assert_eq2", "Not Deprecated", "abc", None, "module_name", None),
+        Lint::new("should_assert_eq2", "internal", "abc", None, "module_name", None),
+        Lint::new("should_assert_eq2", "internal_style", "abc", None, "module_name", None)
     ];
     let expected = vec![
-        Lint::new("should_assert_eq2", "Not Deprecated", "abc", None, "module_name")
+        Lint::new("should_assert_eq2", "Not Deprecated", "abc", None, "module_name", None)
     ];
     assert_eq!(expected, Lint::usable_lints(lints.into_iter()).collect::<Vec<Lint>>());
 }
@@ -312,17 +524,17 @@ fn test_usable_lints() {
 #[test]
 fn test_by_lint_group() {
     let lints = vec![
-        Lint::new("should_assert_eq", "group1", "abc", None, "module_name"),
-        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name"),
-        Lint::new("incorrect_match", "group1", "abc", None, "module_name"),
+        Lint::new("should_assert_eq", "group1", "abc", None, "module_name", None),
+        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name", None),
+        Lint::new("incorrect_match", "group1", "abc", None, "module_name", None),
     ];
     let mut expected: HashMap<String, Vec<Lint>> = HashMap::new();
     expected.insert("group1".to_string(), vec![
-        Lint::new("should_assert_eq", "group1", "abc", None, "module_name"),
-        Lint::new("incorrect_match", "group1", "abc", None, "module_name"),
+        Lint::new("should_assert_eq", "group1", "abc", None, "module_name", None),
+        Lint::new("incorrect_match", "group1", "abc", None, "module_name", None),
     ]);
     expected.insert("group2".to_string(), vec![
-        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name")
+        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name", None)
     ]);
     assert_eq!(expected, Lint::by_lint_group(&lints));
 }
@@ -330,9 +542,9 @@ fn test_by_lint_group() {
 #[test]
 fn test_gen_changelog_lint_list() {
     let lints = vec![
-        Lint::new("should_assert_eq", "group1", "abc", None, "module_name"),
-        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name"),
-        Lint::new("incorrect_internal", "internal_style", "abc", None, "module_name"),
+        Lint::new("should_assert_eq", "group1", "abc", None, "module_name", None),
+        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name", None),
+        Lint::new("incorrect_internal", "internal_style", "abc", None, "module_name", None),
     ];
     let expected = vec![
         format!("[`should_assert_eq`]: {}#should_assert_eq", DOCS_LINK.to_string()),
@@ -341,11 +553,46 @@ fn test_gen_changelog_lint_list() {
     assert_eq!(expected, gen_changelog_lint_list(lints));
 }
 
+#[test]
+fn test_gen_versions_lint_list() {
+    let lints = vec![
+        Lint::new("should_assert_eq", "group1", "abc", None, "module_name", Some("1.30.0")),
+        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name", Some("1.29.0")),
+        Lint::new("no_version", "group2", "abc", None, "module_name", None),
+    ];
+    let expected = vec![
+        "## 1.29.0".to_string(),
+        format!("* [`should_assert_eq2`]: {}#should_assert_eq2", DOCS_LINK.to_string()),
+        "## 1.30.0".to_string(),
+        format!("* [`should_assert_eq`]: {}#should_assert_eq", DOCS_LINK.to_string()),
+        "## Unknown".to_string(),
+        format!("* [`no_version`]: {}#no_version", DOCS_LINK.to_string()),
+    ];
+    assert_eq!(expected, gen_versions_lint_list(lints));
+}
+
+#[test]
+fn test_gen_versions_lint_list_sorts_numerically_across_digit_widths() {
+    // A lexicographic sort would put "1.10.0" before "1.9.0"; make sure the
+    // generator sorts these as the versions they actually are.
+    let lints = vec![
+        Lint::new("new_in_1_10", "group1", "abc", None, "module_name", Some("1.10.0")),
+        Lint::new("new_in_1_9", "group1", "abc", None, "module_name", Some("1.9.0")),
+    ];
+    let expected = vec![
+        "## 1.9.0".to_string(),
+        format!("* [`new_in_1_9`]: {}#new_in_1_9", DOCS_LINK.to_string()),
+        "## 1.10.0".to_string(),
+        format!("* [`new_in_1_10`]: {}#new_in_1_10", DOCS_LINK.to_string()),
+    ];
+    assert_eq!(expected, gen_versions_lint_list(lints));
+}
+
 #[test]
 fn test_gen_deprecated() {
     let lints = vec![
-        Lint::new("should_assert_eq", "group1", "abc", Some("has been superseeded by should_assert_eq2"), "module_name"),
-        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name")
+        Lint::new("should_assert_eq", "group1", "abc", Some("has been superseeded by should_assert_eq2"), "module_name", None),
+        Lint::new("should_assert_eq2", "group2", "abc", None, "module_name", None)
     ];
     let expected: Vec<String> = vec![
         r#"    store.register_removed(
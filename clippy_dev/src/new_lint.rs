@@ -0,0 +1,197 @@
+use crate::update_lints::update_lints;
+use crate::replace_region_in_file;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The lint groups a new lint can be scaffolded into. Kept in sync with the
+/// groups accepted by `declare_clippy_lint!` in `clippy_lints/src/lib.rs`.
+const LINT_CATEGORIES: &[&str] = &[
+    "style",
+    "correctness",
+    "complexity",
+    "perf",
+    "pedantic",
+    "restriction",
+    "cargo",
+    "nursery",
+];
+
+/// Which lint pass the generated stub should implement.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LintPass {
+    Early,
+    Late,
+}
+
+impl LintPass {
+    fn trait_name(self) -> &'static str {
+        match self {
+            LintPass::Early => "EarlyLintPass",
+            LintPass::Late => "LateLintPass",
+        }
+    }
+}
+
+/// Scaffolds a brand-new lint named `name` in the given `category`,
+/// implementing `pass`. This creates `clippy_lints/src/<name>.rs` with a
+/// filled-in `declare_clippy_lint!` block and a stub handler, creates the
+/// matching `tests/ui/<name>.rs`, and wires the new module into
+/// `clippy_lints/src/lib.rs`.
+pub fn create(name: &str, category: &str, pass: LintPass) -> io::Result<()> {
+    if !LINT_CATEGORIES.contains(&category) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` is not a known lint category, expected one of {:?}", category, LINT_CATEGORIES),
+        ));
+    }
+
+    create_lint_module(name, category, pass)?;
+    create_test(name)?;
+    // The module and lint-metadata registrations are fully derivable from
+    // the gathered lints, so let `update_lints` regenerate them. Only the
+    // lint *pass* registration needs to be added here, since the pass type
+    // isn't recorded anywhere `update_lints` can see.
+    update_lints();
+    add_pass_registration(name, pass);
+
+    println!("Generated lint `{}`. Don't forget to update its description once it does something!", name);
+    Ok(())
+}
+
+fn create_lint_module(name: &str, category: &str, pass: LintPass) -> io::Result<()> {
+    let path = format!("../clippy_lints/src/{}.rs", name);
+    if Path::new(&path).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("lint module `{}` already exists, refusing to overwrite it", path),
+        ));
+    }
+
+    let upper_name = name.to_uppercase();
+    let struct_name = to_camel_case(name);
+    let trait_name = pass.trait_name();
+
+    let content = format!(
+        "use rustc_lint::{{{trait_name}, LintContext}};
+use rustc_session::{{declare_lint_pass, declare_tool_lint}};
+
+declare_clippy_lint! {{
+    /// ### What it does
+    ///
+    /// ### Why is this bad?
+    ///
+    /// ### Example
+    /// ```rust
+    /// // example code where clippy issues a warning
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// // example code which does not raise clippy warning
+    /// ```
+    #[clippy::version = \"\"]
+    pub {upper_name},
+    {category},
+    \"default lint description\"
+}}
+
+declare_lint_pass!({struct_name} => [{upper_name}]);
+
+impl {trait_name} for {struct_name} {{}}
+",
+        trait_name = trait_name,
+        upper_name = upper_name,
+        category = category,
+        struct_name = struct_name,
+    );
+
+    fs::write(&path, content)
+}
+
+fn create_test(name: &str) -> io::Result<()> {
+    let path = format!("../tests/ui/{}.rs", name);
+    if Path::new(&path).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("test `{}` already exists, refusing to overwrite it", path),
+        ));
+    }
+
+    fs::write(&path, "#![warn(clippy::all)]\n\nfn main() {\n    // test code goes here\n}\n")
+}
+
+const PASSES_REGION_START: &str = "^// begin register passes, do not remove this comment, it's used in `new_lint`";
+const PASSES_REGION_END: &str = "^// end register passes, do not remove this comment, it's used in `new_lint`";
+
+/// Inserts `store.register_{early,late}_pass(|| box <struct>::default());`
+/// into the alphabetically sorted block of pass registrations in
+/// `clippy_lints/src/lib.rs`. Unlike `update_lints`'s generators, the pass
+/// type of an existing lint can't be recovered from `gather_all()`, so this
+/// has to read whatever registrations are already there and append to them
+/// rather than rebuilding the region from scratch.
+fn add_pass_registration(name: &str, pass: LintPass) {
+    let struct_name = to_camel_case(name);
+    let register_call = match pass {
+        LintPass::Early => format!("    store.register_early_pass(|| box {}::default());", struct_name),
+        LintPass::Late => format!("    store.register_late_pass(|| box {}::default());", struct_name),
+    };
+
+    let mut registrations = read_region_lines("../clippy_lints/src/lib.rs", PASSES_REGION_START, PASSES_REGION_END);
+    registrations.push(register_call);
+    registrations.sort();
+
+    replace_region_in_file("../clippy_lints/src/lib.rs", PASSES_REGION_START, PASSES_REGION_END, false, || {
+        registrations.clone()
+    });
+}
+
+/// Returns the lines already present between the `start` and `end` marker
+/// lines in `path`, excluding the markers themselves. Returns an empty `Vec`
+/// if the file or the region doesn't exist yet.
+fn read_region_lines(path: &str, start: &str, end: &str) -> Vec<String> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let start = Regex::new(start).unwrap();
+    let end = Regex::new(end).unwrap();
+
+    let mut lines = Vec::new();
+    let mut in_region = false;
+    for line in contents.lines() {
+        if in_region {
+            if end.is_match(line) {
+                break;
+            }
+            lines.push(line.to_string());
+        } else if start.is_match(line) {
+            in_region = true;
+        }
+    }
+    lines
+}
+
+/// Converts a `snake_case` lint name into the `CamelCase` struct name used
+/// for its lint pass, e.g. `foo_bar` -> `FooBar`.
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_camel_case() {
+        assert_eq!(to_camel_case("foo_bar"), "FooBar");
+        assert_eq!(to_camel_case("single"), "Single");
+        assert_eq!(to_camel_case("_leading_underscore"), "LeadingUnderscore");
+        assert_eq!(to_camel_case("trailing_underscore_"), "TrailingUnderscore");
+    }
+}
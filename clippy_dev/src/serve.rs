@@ -0,0 +1,121 @@
+use crate::{gather_all, lint_files, Lint, DOCS_LINK};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Response, Server};
+
+/// How often the watcher re-checks `clippy_lints/src` for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Serves the generated lint documentation page over a local HTTP server,
+/// watching `clippy_lints/src` and regenerating the page whenever a `.rs`
+/// file in it changes. This lets lint authors preview how their
+/// `declare_clippy_lint!` description and group assignment will render
+/// without pushing to the hosted site.
+pub fn serve(bind_address: &str, port: u16) {
+    let page = Arc::new(Mutex::new(render_index()));
+
+    {
+        let page = Arc::clone(&page);
+        thread::spawn(move || watch_and_rerender(page));
+    }
+
+    let server = Server::http((bind_address, port)).expect("could not start the docs server");
+    println!("Serving clippy's lint docs on http://{}:{}", bind_address, port);
+
+    for request in server.incoming_requests() {
+        let body = page.lock().unwrap().clone();
+        let response = Response::from_string(body).with_header(
+            "Content-Type: text/html; charset=utf-8".parse::<tiny_http::Header>().unwrap(),
+        );
+        let _ = request.respond(response);
+    }
+}
+
+/// Polls the hash of every `.rs` file under `clippy_lints/src` and
+/// regenerates `page` whenever it changes.
+fn watch_and_rerender(page: Arc<Mutex<String>>) {
+    let mut last_hash = hash_source_tree();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let hash = hash_source_tree();
+        if hash != last_hash {
+            last_hash = hash;
+            *page.lock().unwrap() = render_index();
+        }
+    }
+}
+
+/// Hashes the path and modification time of every lint source file, so a
+/// change anywhere in the watched tree is picked up without re-parsing it.
+fn hash_source_tree() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for entry in lint_files() {
+        entry.path().hash(&mut hasher);
+        if let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) {
+            modified.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Renders the current set of usable lints, sorted by name, into an
+/// `index.html` page: a single table of name, group and description, each
+/// name linking to the lint's entry in the hosted docs.
+fn render_index() -> String {
+    let mut lints: Vec<Lint> = Lint::usable_lints(gather_all()).collect();
+    lints.sort_by_key(|l| l.name.clone());
+    render_lints_table(&lints)
+}
+
+/// Renders `lints` into the `<table>` based `index.html` page. Pulled out of
+/// `render_index` so the markup itself can be unit-tested without touching
+/// the filesystem.
+fn render_lints_table(lints: &[Lint]) -> String {
+    let rows: String = lints
+        .iter()
+        .map(|l| {
+            format!(
+                "<tr><td><a href=\"{link}#{name}\">{name}</a></td><td>{group}</td><td>{desc}</td></tr>",
+                link = DOCS_LINK.as_str(),
+                name = l.name,
+                group = l.group,
+                desc = html_escape(&l.desc),
+            )
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html>\n<html><head><title>Clippy Lints</title></head><body>\n\
+         <table><tr><th>name</th><th>group</th><th>description</th></tr>\n{}\n</table>\n\
+         </body></html>\n",
+        rows
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(html_escape("a & b"), "a &amp; b");
+        assert_eq!(html_escape("<script>"), "&lt;script&gt;");
+        assert_eq!(html_escape("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_render_lints_table() {
+        let lints = vec![Lint::new("foo_lint", "style", "uses <b> & stuff", None, "foo", None)];
+        let html = render_lints_table(&lints);
+        assert!(html.contains("<a href=\"https://rust-lang-nursery.github.io/rust-clippy/master/index.html#foo_lint\">foo_lint</a>"));
+        assert!(html.contains("<td>style</td>"));
+        assert!(html.contains("uses &lt;b&gt; &amp; stuff"));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{gather_all, gen_deprecated, replace_region_in_file, Lint};
+
+/// Rewrites the machine-generated, delimited regions of
+/// `clippy_lints/src/lib.rs`: the sorted `mod <module>;` declarations, the
+/// `store.register_*` group registrations (grouped by lint group), and the
+/// deprecated registrations. Every list is sorted by lint or module name so
+/// re-running this command on an unchanged tree produces no diff.
+pub fn update_lints() {
+    let lints: Vec<Lint> = gather_all().collect();
+
+    update_mod_declarations(&lints);
+    update_lint_group_registrations(&lints);
+    update_deprecated_registrations(&lints);
+}
+
+fn update_mod_declarations(lints: &[Lint]) {
+    let lines = mod_declaration_lines(lints);
+    replace_region_in_file(
+        "../clippy_lints/src/lib.rs",
+        "^// begin lints modules, do not remove this comment, it's used in `update_lints`",
+        "^// end lints modules, do not remove this comment, it's used in `update_lints`",
+        false,
+        || lines.clone(),
+    );
+}
+
+/// The sorted, deduplicated `mod <module>;` lines for every module that
+/// contains at least one lint.
+fn mod_declaration_lines(lints: &[Lint]) -> Vec<String> {
+    let mut modules: Vec<String> = lints.iter().map(|l| l.module.clone()).collect();
+    modules.sort();
+    modules.dedup();
+    modules.iter().map(|module| format!("mod {};", module)).collect()
+}
+
+fn update_lint_group_registrations(lints: &[Lint]) {
+    let lines = lint_group_registration_lines(lints);
+    replace_region_in_file(
+        "../clippy_lints/src/lib.rs",
+        "^// begin register lints, do not remove this comment, it's used in `update_lints`",
+        "^// end register lints, do not remove this comment, it's used in `update_lints`",
+        false,
+        || lines.clone(),
+    );
+}
+
+/// The `store.register_lint(&module::NAME);` lines for every usable lint,
+/// grouped by lint group and sorted by group then by lint name.
+fn lint_group_registration_lines(lints: &[Lint]) -> Vec<String> {
+    let usable_lints: Vec<Lint> = Lint::usable_lints(lints.iter().cloned()).collect();
+    let grouped = Lint::by_lint_group(&usable_lints);
+
+    let mut groups: Vec<&String> = grouped.keys().collect();
+    groups.sort();
+
+    groups
+        .into_iter()
+        .flat_map(|group| {
+            let mut lints_in_group = grouped[group].clone();
+            lints_in_group.sort_by_key(|l| l.name.clone());
+            lints_in_group
+                .iter()
+                .map(|l| format!("    store.register_lint(&{}::{});", l.module, l.name.to_uppercase()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn update_deprecated_registrations(lints: &[Lint]) {
+    let mut deprecated: Vec<Lint> = lints.iter().filter(|l| l.deprecation.is_some()).cloned().collect();
+    deprecated.sort_by_key(|l| l.name.clone());
+    let registrations = gen_deprecated(&deprecated);
+
+    replace_region_in_file(
+        "../clippy_lints/src/lib.rs",
+        "^// begin deprecated lints, do not remove this comment, it's used in `update_lints`",
+        "^// end deprecated lints, do not remove this comment, it's used in `update_lints`",
+        false,
+        || registrations.clone(),
+    );
+}
+
+/// Returns `content` with the generated-file header prepended, for output
+/// files that consist entirely of generated code.
+pub fn with_generated_file_header(content: &str) -> String {
+    format!(
+        "// This file was generated by `cargo dev update_lints`.\n\
+         // Use that command to update this file and do not edit by hand.\n\
+         // Manual edits will be overwritten.\n\n{}",
+        content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_declaration_lines() {
+        let lints = vec![
+            Lint::new("foo", "style", "abc", None, "foo", None),
+            Lint::new("bar", "style", "abc", None, "bar", None),
+            Lint::new("bar2", "style", "abc", None, "bar", None),
+        ];
+        let expected = vec!["mod bar;".to_string(), "mod foo;".to_string()];
+        assert_eq!(expected, mod_declaration_lines(&lints));
+    }
+
+    #[test]
+    fn test_lint_group_registration_lines() {
+        let lints = vec![
+            Lint::new("foo", "style", "abc", None, "foo", None),
+            Lint::new("bar", "complexity", "abc", None, "bar", None),
+            Lint::new("internal_lint", "internal", "abc", None, "internal", None),
+            Lint::new("deprecated_lint", "Deprecated", "abc", Some("old"), "old", None),
+        ];
+        let expected = vec![
+            "    store.register_lint(&bar::BAR);".to_string(),
+            "    store.register_lint(&foo::FOO);".to_string(),
+        ];
+        assert_eq!(expected, lint_group_registration_lines(&lints));
+    }
+}